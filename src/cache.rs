@@ -1,65 +1,149 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
 use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
 
 use super::cache_item::CacheItem;
 use super::config::CacheConfig;
-use aws_sdk_config::error::SdkError;
-use aws_sdk_secretsmanager::operation::get_secret_value::GetSecretValueError;
+use super::error::SecretCacheError;
+use super::store::{LruSecretStore, SecretStore};
+use super::time_source::TimeSource;
 use aws_sdk_secretsmanager::{Client as SecretsManagerClient};
 use lru::LruCache;
+use serde::de::DeserializeOwned;
 
 /// Client for in-process caching of secret values from AWS Secrets Manager.
 ///
-/// An LRU (least-recently used) caching scheme is used that provides
-/// O(1) insertions and O(1) lookups for cached values.
-pub struct SecretCache {
+/// Secret strings (and JSON secrets, which share the same underlying entries) are cached behind
+/// a pluggable [`SecretStore`], defaulting to an LRU (least-recently used) caching scheme that
+/// provides O(1) insertions and O(1) lookups. Binary secrets are cached separately.
+pub struct SecretCache<S: SecretStore = LruSecretStore> {
     client: SecretsManagerClient,
-    config: CacheConfig,
-    cache: LruCache<String, CacheItem<String>>,
+    config: Arc<CacheConfig>,
+    store: Arc<S>,
+    binary_cache: LruCache<String, CacheItem<Vec<u8>>>,
+
+    /// The cache_keys with a background refresh currently in flight, used to ensure only one
+    /// refresh per cache_key is spawned at a time.
+    refreshing: Arc<Mutex<HashSet<String>>>,
 }
 
-impl SecretCache {
-    /// Returns a new SecretsCache using the default Cache Configuration options.
+impl SecretCache<LruSecretStore> {
+    /// Returns a new SecretsCache using the default Cache Configuration options and the default
+    /// LRU-backed SecretStore.
     pub fn new(client: SecretsManagerClient) -> Self {
-        SecretCache::new_cache(client, CacheConfig::new())
+        SecretCache::new_with_config(client, CacheConfig::new())
     }
 
-    /// Returns a new SecretsCache using a provided custom Cache Configuration.
+    /// Returns a new SecretsCache using a provided custom Cache Configuration and the default
+    /// LRU-backed SecretStore.
     pub fn new_with_config(client: SecretsManagerClient, config: CacheConfig) -> Self {
-        SecretCache::new_cache(client, config)
+        let store = LruSecretStore::new(config.max_cache_size);
+        SecretCache::new_with_store(client, config, store)
     }
+}
 
-    fn new_cache(client: SecretsManagerClient, config: CacheConfig) -> Self {
-        let cache = LruCache::new(
-            NonZeroUsize::new(config.max_cache_size)
-                .unwrap_or(NonZeroUsize::new(1).expect("Default max_cache_size must be non-zero")),
-        );
+impl<S: SecretStore + 'static> SecretCache<S> {
+    /// Returns a new SecretsCache backed by a custom SecretStore, for plugging in alternate
+    /// storage backends (e.g. an unbounded map, or a store shared across a multi-threaded
+    /// Lambda runtime).
+    pub fn new_with_store(client: SecretsManagerClient, config: CacheConfig, store: S) -> Self {
+        let max_cache_size = NonZeroUsize::new(config.max_cache_size)
+            .unwrap_or(NonZeroUsize::new(1).expect("Default max_cache_size must be non-zero"));
+        let binary_cache = LruCache::new(max_cache_size);
         Self {
             client,
-            config,
-            cache,
+            config: Arc::new(config),
+            store: Arc::new(store),
+            binary_cache,
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Spawns a background task that refreshes `cache_key` (the given `version_stage` of
+    /// `secret_id`, or `version_id` when set) and updates the store, unless a refresh for `cache_key` is already
+    /// in flight.
+    fn spawn_background_refresh(
+        &self,
+        cache_key: String,
+        secret_id: String,
+        version_stage: String,
+        version_id: Option<String>,
+    ) {
+        let mut refreshing = self.refreshing.lock().unwrap();
+        if !refreshing.insert(cache_key.clone()) {
+            return;
         }
+        drop(refreshing);
+
+        let client = self.client.clone();
+        let config = Arc::clone(&self.config);
+        let store = Arc::clone(&self.store);
+        let refreshing = Arc::clone(&self.refreshing);
+
+        tokio::spawn(async move {
+            let mut request = client.get_secret_value().secret_id(secret_id);
+            request = match &version_id {
+                Some(version_id) => request.version_id(version_id.clone()),
+                None => request.version_stage(version_stage),
+            };
+
+            let result = request.send().await;
+
+            if let Ok(resp) = result {
+                if let Some(secret_value) = resp.secret_string {
+                    let now_nanos = config.time_source.now_nanos();
+                    let cache_item = CacheItem::new(secret_value, config.cache_item_ttl, now_nanos);
+                    store.put(cache_key.clone(), cache_item);
+                }
+            }
+
+            refreshing.lock().unwrap().remove(&cache_key);
+        });
     }
 
     /// Returns a builder for getting secret strings.
     ///
     /// Retrieve the secret value with send()
-    pub fn get_secret_string(&mut self, secret_id: String) -> GetSecretStringBuilder {
+    pub fn get_secret_string(&mut self, secret_id: String) -> GetSecretStringBuilder<S> {
         GetSecretStringBuilder::new(self, secret_id)
     }
+
+    /// Returns a builder for getting secrets deserialized from JSON into a caller-provided type.
+    ///
+    /// The raw secret string is cached the same way as [`SecretCache::get_secret_string`], so
+    /// both methods share cache entries for the same secret_id. Deserialization happens on every
+    /// call to send().
+    pub fn get_secret_json<T: DeserializeOwned>(&mut self, secret_id: String) -> GetSecretJsonBuilder<T, S> {
+        GetSecretJsonBuilder::new(self, secret_id)
+    }
+
+    /// Returns a builder for getting binary secrets.
+    ///
+    /// Use this for secrets stored via the `SecretBinary` field, such as certificates or keys.
+    /// Retrieve the secret value with send()
+    pub fn get_secret_binary(&mut self, secret_id: String) -> GetSecretBinaryBuilder<S> {
+        GetSecretBinaryBuilder::new(self, secret_id)
+    }
 }
 
 /// A builder for the get_secret_string method.
-pub struct GetSecretStringBuilder<'a> {
-    secret_cache: &'a mut SecretCache,
+pub struct GetSecretStringBuilder<'a, S: SecretStore = LruSecretStore> {
+    secret_cache: &'a mut SecretCache<S>,
     secret_id: String,
+    version_stage: String,
+    version_id: Option<String>,
     force_refresh: bool,
 }
 
-impl<'a> GetSecretStringBuilder<'a> {
-    pub fn new(secret_cache: &'a mut SecretCache, secret_id: String) -> Self {
+impl<'a, S: SecretStore + 'static> GetSecretStringBuilder<'a, S> {
+    pub fn new(secret_cache: &'a mut SecretCache<S>, secret_id: String) -> Self {
+        let version_stage = secret_cache.config.version_stage.clone();
         GetSecretStringBuilder {
             secret_cache,
             secret_id,
+            version_stage,
+            version_id: None,
             force_refresh: false,
         }
     }
@@ -73,6 +157,39 @@ impl<'a> GetSecretStringBuilder<'a> {
         self
     }
 
+    /// Sets the version stage to fetch, overriding the version_stage from the CacheConfig.
+    ///
+    /// The version_stage forms part of the cache key, so distinct stages (e.g. `AWSCURRENT` and
+    /// `AWSPREVIOUS`) are cached independently and don't collide.
+    pub fn version_stage(mut self, version_stage: String) -> Self {
+        self.version_stage = version_stage;
+        self
+    }
+
+    /// Fetches a specific pinned version of the secret, identified by its version_id.
+    ///
+    /// When set, version_id takes precedence over version_stage when calling AWS Secrets
+    /// Manager, and forms part of the cache key instead of version_stage.
+    pub fn version_id(mut self, version_id: String) -> Self {
+        self.version_id = Some(version_id);
+        self
+    }
+
+    /// Returns the composite cache key for this builder's secret_id and version stage/id, so
+    /// distinct versions of the same secret don't collide in the store.
+    ///
+    /// The secret_id is length-prefixed because it is commonly a full ARN and may itself
+    /// contain colons, which would otherwise make the boundary between secret_id and the
+    /// version stage/id ambiguous (e.g. `secret_id="a:b", stage="c"` colliding with
+    /// `secret_id="a", stage="b:c"`).
+    fn cache_key(&self) -> String {
+        let version = match &self.version_id {
+            Some(version_id) => version_id.as_str(),
+            None => self.version_stage.as_str(),
+        };
+        format!("{}:{}:{}", self.secret_id.len(), self.secret_id, version)
+    }
+
     /// Fetches the secret value from the cache.
     ///
     /// If the secret value exists in the cache and hasn't expired it will be immediately returned.
@@ -82,43 +199,155 @@ impl<'a> GetSecretStringBuilder<'a> {
     /// - the force_refresh option was provided
     ///
     /// Values are stored in the cache with the cache_item_ttl from the CacheConfig.
-    pub async fn send(&mut self) -> Result<String, SdkError<GetSecretValueError>> {
+    ///
+    /// If a refresh_interval is configured and the cached item is older than the interval but
+    /// still within its TTL, the cached value is returned immediately and the entry is refreshed
+    /// in the background.
+    pub async fn send(&mut self) -> Result<String, SecretCacheError> {
+        let now_nanos = self.secret_cache.config.time_source.now_nanos();
+        let cache_key = self.cache_key();
+
         if !self.force_refresh {
-            if let Some(cache_item) = self.secret_cache.cache.get(&self.secret_id) {
-                if !cache_item.is_expired() {
-                    return Ok(cache_item.value.clone());
+            if let Some(cache_item) = self.secret_cache.store.get(&cache_key) {
+                if !cache_item.is_expired(now_nanos) {
+                    if let Some(refresh_interval) = self.secret_cache.config.refresh_interval {
+                        if cache_item.is_stale(now_nanos, refresh_interval) {
+                            self.secret_cache.spawn_background_refresh(
+                                cache_key,
+                                self.secret_id.clone(),
+                                self.version_stage.clone(),
+                                self.version_id.clone(),
+                            );
+                        }
+                    }
+                    return Ok(cache_item.value);
                 }
             }
         }
 
-        match self.fetch_secret().await {
-            Ok(secret_value) => {
-                let cache_item = CacheItem::new(
-                    secret_value.clone(),
-                    self.secret_cache.config.cache_item_ttl,
-                );
-                self.secret_cache
-                    .cache
-                    .put(self.secret_id.clone(), cache_item);
-                Ok(secret_value)
+        let secret_value = self.fetch_secret().await?;
+        let cache_item = CacheItem::new(secret_value.clone(), self.secret_cache.config.cache_item_ttl, now_nanos);
+        self.secret_cache.store.put(cache_key, cache_item);
+        Ok(secret_value)
+    }
+
+    async fn fetch_secret(&mut self) -> Result<String, SecretCacheError> {
+        let mut request = self
+            .secret_cache
+            .client
+            .get_secret_value()
+            .secret_id(self.secret_id.clone());
+        request = match &self.version_id {
+            Some(version_id) => request.version_id(version_id.clone()),
+            None => request.version_stage(self.version_stage.clone()),
+        };
+
+        let resp = request.send().await?;
+
+        resp.secret_string.ok_or(SecretCacheError::MissingSecretString)
+    }
+}
+
+/// A builder for the get_secret_json method.
+pub struct GetSecretJsonBuilder<'a, T, S: SecretStore = LruSecretStore> {
+    string_builder: GetSecretStringBuilder<'a, S>,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T: DeserializeOwned, S: SecretStore + 'static> GetSecretJsonBuilder<'a, T, S> {
+    pub fn new(secret_cache: &'a mut SecretCache<S>, secret_id: String) -> Self {
+        GetSecretJsonBuilder {
+            string_builder: GetSecretStringBuilder::new(secret_cache, secret_id),
+            marker: PhantomData,
+        }
+    }
+
+    /// Forces a refresh of the secret.
+    ///
+    /// Forces the secret to be fetched from AWS and updates the cache with the fresh value.
+    /// This is required when the cached secret is out of date but not expired, for example due to rotation.
+    pub fn force_refresh(mut self) -> Self {
+        self.string_builder = self.string_builder.force_refresh();
+        self
+    }
+
+    /// Fetches the secret value from the cache and deserializes it from JSON into `T`.
+    ///
+    /// The raw secret string is cached using the same rules as [`GetSecretStringBuilder::send`];
+    /// deserialization is performed fresh on every call.
+    pub async fn send(&mut self) -> Result<T, SecretCacheError> {
+        let secret_value = self.string_builder.send().await?;
+        let value = serde_json::from_str(&secret_value)?;
+        Ok(value)
+    }
+}
+
+/// A builder for the get_secret_binary method.
+pub struct GetSecretBinaryBuilder<'a, S: SecretStore = LruSecretStore> {
+    secret_cache: &'a mut SecretCache<S>,
+    secret_id: String,
+    force_refresh: bool,
+}
+
+impl<'a, S: SecretStore + 'static> GetSecretBinaryBuilder<'a, S> {
+    pub fn new(secret_cache: &'a mut SecretCache<S>, secret_id: String) -> Self {
+        GetSecretBinaryBuilder {
+            secret_cache,
+            secret_id,
+            force_refresh: false,
+        }
+    }
+
+    /// Forces a refresh of the secret.
+    ///
+    /// Forces the secret to be fetched from AWS and updates the cache with the fresh value.
+    /// This is required when the cached secret is out of date but not expired, for example due to rotation.
+    pub fn force_refresh(mut self) -> Self {
+        self.force_refresh = true;
+        self
+    }
+
+    /// Fetches the binary secret value from the cache.
+    ///
+    /// If the secret value exists in the cache and hasn't expired it will be immediately returned.
+    /// The secret will be fetched by calling AWS Secrets Manager and updated in the cache if:
+    /// - the secret value hasn't been stored in the cache
+    /// - the secret stored in the cache but has expired
+    /// - the force_refresh option was provided
+    ///
+    /// Values are stored in the cache with the cache_item_ttl from the CacheConfig.
+    pub async fn send(&mut self) -> Result<Vec<u8>, SecretCacheError> {
+        let now_nanos = self.secret_cache.config.time_source.now_nanos();
+
+        if !self.force_refresh {
+            if let Some(cache_item) = self.secret_cache.binary_cache.get(&self.secret_id) {
+                if !cache_item.is_expired(now_nanos) {
+                    return Ok(cache_item.value.clone());
+                }
             }
-            Err(e) => Err(e),
         }
+
+        let secret_value = self.fetch_secret().await?;
+        let cache_item = CacheItem::new(secret_value.clone(), self.secret_cache.config.cache_item_ttl, now_nanos);
+        self.secret_cache
+            .binary_cache
+            .put(self.secret_id.clone(), cache_item);
+        Ok(secret_value)
     }
 
-    async fn fetch_secret(&mut self) -> Result<String, SdkError<GetSecretValueError>> {
-        match self
+    async fn fetch_secret(&mut self) -> Result<Vec<u8>, SecretCacheError> {
+        let resp = self
             .secret_cache
             .client
             .get_secret_value()
             .secret_id(self.secret_id.clone())
             .version_stage(self.secret_cache.config.version_stage.clone())
             .send()
-            .await
-        {
-            Ok(resp) => return Ok(resp.secret_string.as_deref().unwrap().to_string()),
-            Err(e) => Err(e),
-        }
+            .await?;
+
+        resp.secret_binary
+            .map(|blob| blob.into_inner())
+            .ok_or(SecretCacheError::MissingSecretBinary)
     }
 }
 
@@ -127,6 +356,32 @@ mod tests {
     use super::*;
     use aws_sdk_secretsmanager::{Client as SecretsManagerClient, Config};
     use aws_sdk_config::config::{Credentials, Region};
+    use crate::time_source::TestTimeSource;
+    use std::collections::HashMap;
+
+    // a minimal SecretStore used to exercise send() against a cached value without making a
+    // real call to AWS Secrets Manager
+    struct FakeSecretStore {
+        items: Mutex<HashMap<String, CacheItem<String>>>,
+    }
+
+    impl FakeSecretStore {
+        fn new() -> Self {
+            FakeSecretStore {
+                items: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    impl SecretStore for FakeSecretStore {
+        fn get(&self, key: &str) -> Option<CacheItem<String>> {
+            self.items.lock().unwrap().get(key).cloned()
+        }
+
+        fn put(&self, key: String, item: CacheItem<String>) {
+            self.items.lock().unwrap().insert(key, item);
+        }
+    }
 
     #[test]
     fn get_secret_string_builder_defaults() {
@@ -136,9 +391,53 @@ mod tests {
         let builder = GetSecretStringBuilder::new(&mut secret_cache, "service/secret".to_string());
 
         assert_eq!(builder.secret_id, "service/secret");
+        assert_eq!(builder.version_stage, "AWSCURRENT");
+        assert_eq!(builder.version_id, None);
         assert!(!builder.force_refresh);
     }
 
+    #[test]
+    fn get_secret_string_builder_version_stage() {
+        let mock_secrets_manager_client = get_mock_secretsmanager_client();
+        let mut secret_cache = SecretCache::new(mock_secrets_manager_client);
+
+        let builder = GetSecretStringBuilder::new(&mut secret_cache, "service/secret".to_string())
+            .version_stage("AWSPREVIOUS".to_string());
+
+        assert_eq!(builder.version_stage, "AWSPREVIOUS");
+        assert_eq!(builder.cache_key(), "14:service/secret:AWSPREVIOUS");
+    }
+
+    #[test]
+    fn get_secret_string_builder_version_id() {
+        let mock_secrets_manager_client = get_mock_secretsmanager_client();
+        let mut secret_cache = SecretCache::new(mock_secrets_manager_client);
+
+        let builder = GetSecretStringBuilder::new(&mut secret_cache, "service/secret".to_string())
+            .version_id("version-id".to_string());
+
+        assert_eq!(builder.version_id, Some("version-id".to_string()));
+        assert_eq!(builder.cache_key(), "14:service/secret:version-id");
+    }
+
+    #[test]
+    fn get_secret_string_builder_cache_key_disambiguates_colons_in_secret_id() {
+        let mock_secrets_manager_client = get_mock_secretsmanager_client();
+        let mut secret_cache = SecretCache::new(mock_secrets_manager_client);
+
+        // "a:b" + stage "c" must not collide in the store with "a" + stage "b:c"
+        let builder_a = GetSecretStringBuilder::new(&mut secret_cache, "a:b".to_string())
+            .version_stage("c".to_string());
+        let cache_key_a = builder_a.cache_key();
+
+        let mut secret_cache = SecretCache::new(get_mock_secretsmanager_client());
+        let builder_b = GetSecretStringBuilder::new(&mut secret_cache, "a".to_string())
+            .version_stage("b:c".to_string());
+        let cache_key_b = builder_b.cache_key();
+
+        assert_ne!(cache_key_a, cache_key_b);
+    }
+
     #[test]
     fn get_secret_string_builder_force_refresh() {
         let mock_secrets_manager_client = get_mock_secretsmanager_client();
@@ -151,6 +450,102 @@ mod tests {
         assert!(builder.force_refresh);
     }
 
+    #[test]
+    fn get_secret_binary_builder_defaults() {
+        let mock_secrets_manager_client = get_mock_secretsmanager_client();
+        let mut secret_cache = SecretCache::new(mock_secrets_manager_client);
+
+        let builder = GetSecretBinaryBuilder::new(&mut secret_cache, "service/secret".to_string());
+
+        assert_eq!(builder.secret_id, "service/secret");
+        assert!(!builder.force_refresh);
+    }
+
+    #[test]
+    fn get_secret_binary_builder_force_refresh() {
+        let mock_secrets_manager_client = get_mock_secretsmanager_client();
+        let mut secret_cache = SecretCache::new(mock_secrets_manager_client);
+
+        let builder = GetSecretBinaryBuilder::new(&mut secret_cache, "service/secret".to_string())
+            .force_refresh();
+
+        assert_eq!(builder.secret_id, "service/secret");
+        assert!(builder.force_refresh);
+    }
+
+    #[tokio::test]
+    async fn get_secret_json_deserializes_cached_value() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct TestSecret {
+            api_key: String,
+        }
+
+        let store = FakeSecretStore::new();
+        let cache_item_ttl: u128 = 3600000000000; // 1 hr in nanoseconds
+        store.put(
+            "14:service/secret:AWSCURRENT".to_string(),
+            CacheItem::new(r#"{"api_key":"abc123"}"#.to_string(), cache_item_ttl, 0),
+        );
+        let config = CacheConfig::new().time_source(Arc::new(TestTimeSource::new(0)));
+        let mut secret_cache = SecretCache::new_with_store(get_mock_secretsmanager_client(), config, store);
+
+        let secret: TestSecret = secret_cache
+            .get_secret_json("service/secret".to_string())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            secret,
+            TestSecret {
+                api_key: "abc123".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn get_secret_json_returns_deserialization_error_for_invalid_json() {
+        #[derive(serde::Deserialize, Debug)]
+        struct TestSecret {
+            #[allow(dead_code)]
+            api_key: String,
+        }
+
+        let store = FakeSecretStore::new();
+        let cache_item_ttl: u128 = 3600000000000; // 1 hr in nanoseconds
+        store.put(
+            "14:service/secret:AWSCURRENT".to_string(),
+            CacheItem::new("not json".to_string(), cache_item_ttl, 0),
+        );
+        let config = CacheConfig::new().time_source(Arc::new(TestTimeSource::new(0)));
+        let mut secret_cache = SecretCache::new_with_store(get_mock_secretsmanager_client(), config, store);
+
+        let result: Result<TestSecret, SecretCacheError> =
+            secret_cache.get_secret_json("service/secret".to_string()).send().await;
+
+        assert!(matches!(result, Err(SecretCacheError::Deserialization(_))));
+    }
+
+    #[tokio::test]
+    async fn get_secret_string_with_custom_store_round_trips_cached_value() {
+        let store = FakeSecretStore::new();
+        let cache_item_ttl: u128 = 3600000000000; // 1 hr in nanoseconds
+        store.put(
+            "14:service/secret:AWSCURRENT".to_string(),
+            CacheItem::new("cached_value".to_string(), cache_item_ttl, 0),
+        );
+        let config = CacheConfig::new().time_source(Arc::new(TestTimeSource::new(0)));
+        let mut secret_cache = SecretCache::new_with_store(get_mock_secretsmanager_client(), config, store);
+
+        let secret_value = secret_cache
+            .get_secret_string("service/secret".to_string())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(secret_value, "cached_value");
+    }
+
     // provides a mocked AWS SecretsManager client for testing
     fn get_mock_secretsmanager_client() -> SecretsManagerClient {
         let conf = Config::builder()
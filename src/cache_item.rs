@@ -1,11 +1,14 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 #[derive(Clone, Copy)]
 /// Stores a cached item value with an expiry TTL.
 pub struct CacheItem<T> {
     /// The item value stored in the cache.
     pub value: T,
 
+    /// The time the cached item was created.
+    ///
+    /// Defined as the number of nanoseconds elapsed since the unix epoch.
+    created_at: u128,
+
     /// The expiry time of the cached item.
     ///
     /// Defined as the number of nanoseconds elapsed since the unix epoch.
@@ -15,56 +18,77 @@ pub struct CacheItem<T> {
 impl<T> CacheItem<T> {
     /// Returns a cached item.
     ///
-    /// Sets the TTL of the item to the current time in nanoseconds since the unix epoch
-    /// plus the provided cache_item_ttl value.
-    pub fn new(value: T, cache_item_ttl: u128) -> Self {
+    /// Sets the TTL of the item to the provided current time (now_nanos, in nanoseconds since
+    /// the unix epoch) plus the provided cache_item_ttl value.
+    pub fn new(value: T, cache_item_ttl: u128, now_nanos: u128) -> Self {
         CacheItem {
             value,
-            ttl: current_time_in_nanoseconds() + cache_item_ttl,
+            created_at: now_nanos,
+            ttl: now_nanos + cache_item_ttl,
         }
     }
 
     /// Determines whether the cached item has expired.
     ///
-    /// Expiration is determined by comparing the current time
-    /// in nanoseconds to the cached item's TTL value.
-    pub fn is_expired(&self) -> bool {
-        current_time_in_nanoseconds() > self.ttl
+    /// Expiration is determined by comparing the provided current time (now_nanos, in
+    /// nanoseconds since the unix epoch) to the cached item's TTL value.
+    pub fn is_expired(&self, now_nanos: u128) -> bool {
+        now_nanos > self.ttl
     }
-}
 
-// Helper function that returns the current nanoseconds since the UNIX epoch
-fn current_time_in_nanoseconds() -> u128 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos()
+    /// Determines whether the cached item is stale, i.e. old enough to warrant a background
+    /// refresh, without yet being expired.
+    ///
+    /// Staleness is determined by comparing the provided current time (now_nanos, in
+    /// nanoseconds since the unix epoch) to the cached item's created_at time plus the provided
+    /// refresh_interval.
+    pub fn is_stale(&self, now_nanos: u128, refresh_interval: u128) -> bool {
+        now_nanos > self.created_at + refresh_interval
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{thread, time};
+    use crate::time_source::{TestTimeSource, TimeSource};
 
     #[test]
     fn cache_item_fresh() {
+        let time_source = TestTimeSource::new(0);
+
         // 1 hr in nanoseconds
         let cache_item_ttl: u128 = 3600000000000;
-        let cache_item = CacheItem::new("secret_value", cache_item_ttl);
+        let cache_item = CacheItem::new("secret_value", cache_item_ttl, time_source.now_nanos());
 
         assert_eq!(cache_item.value, "secret_value");
-        assert_eq!(cache_item.is_expired(), false);
+        assert_eq!(cache_item.is_expired(time_source.now_nanos()), false);
     }
 
     #[test]
     fn cache_item_expired() {
-        let cache_item = CacheItem::new("secret_value", 0);
+        let time_source = TestTimeSource::new(0);
+        let cache_item = CacheItem::new("secret_value", 0, time_source.now_nanos());
 
-        // sleep to simulate value expiring
-        let one_hundred_millis = time::Duration::from_millis(100);
-        thread::sleep(one_hundred_millis);
+        // advance the clock to simulate the value expiring
+        time_source.advance(1);
 
         assert_eq!(cache_item.value, "secret_value");
-        assert_eq!(cache_item.is_expired(), true);
+        assert_eq!(cache_item.is_expired(time_source.now_nanos()), true);
+    }
+
+    #[test]
+    fn cache_item_stale() {
+        let time_source = TestTimeSource::new(0);
+
+        // 1 hr in nanoseconds
+        let cache_item_ttl: u128 = 3600000000000;
+        let cache_item = CacheItem::new("secret_value", cache_item_ttl, time_source.now_nanos());
+
+        // advance the clock to simulate the item aging past the refresh interval while still
+        // within the TTL
+        time_source.advance(1);
+
+        assert_eq!(cache_item.is_stale(time_source.now_nanos(), 0), true);
+        assert_eq!(cache_item.is_expired(time_source.now_nanos()), false);
     }
 }
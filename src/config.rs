@@ -1,3 +1,7 @@
+use std::sync::Arc;
+
+use super::time_source::{SystemTimeSource, TimeSource};
+
 const DEFAULT_MAX_CACHE_SIZE: usize = 1024;
 const DEFAULT_CACHE_ITEM_TTL: u128 = 3600000000000; // 1 hour in nanoseconds
 const DEFAULT_VERSION_STAGE: &str = "AWSCURRENT";
@@ -8,6 +12,7 @@ const DEFAULT_VERSION_STAGE: &str = "AWSCURRENT";
 /// - max_cache_size: 1024
 /// - cache_item_ttl: 3600000000000 (1hr)
 /// - version_stage: "AWSCURRENT"
+/// - refresh_interval: None (refreshing happens synchronously on expiry)
 pub struct CacheConfig {
     /// The maximum number of secrets to maintain in the cache.
     ///
@@ -29,6 +34,23 @@ pub struct CacheConfig {
     ///
     /// Default: "AWSCURRENT"
     pub version_stage: String,
+
+    /// The interval, in nanoseconds, after which a cached-but-not-yet-expired item is
+    /// considered stale.
+    ///
+    /// When set, `send()` returns the stale cached value immediately and spawns a background
+    /// task to refresh the entry, rather than blocking the caller on the Secrets Manager
+    /// round-trip. When `None` (the default), refreshing only ever happens synchronously on
+    /// expiry. This applies to secret strings (and JSON secrets); binary secrets are only
+    /// refreshed synchronously on expiry.
+    ///
+    /// Default: None
+    pub refresh_interval: Option<u128>,
+
+    /// The time source used to determine TTL/expiry and staleness of cached items.
+    ///
+    /// Default: [`SystemTimeSource`]
+    pub time_source: Arc<dyn TimeSource>,
 }
 
 impl CacheConfig {
@@ -38,11 +60,15 @@ impl CacheConfig {
     /// - max_cache_size: 1024
     /// - cache_item_ttl: 3600000000000 (1hr)
     /// - version_stage: "AWSCURRENT"
+    /// - refresh_interval: None (refreshing happens synchronously on expiry)
+    /// - time_source: [`SystemTimeSource`]
     pub fn new() -> Self {
         CacheConfig {
             max_cache_size: DEFAULT_MAX_CACHE_SIZE,
             cache_item_ttl: DEFAULT_CACHE_ITEM_TTL,
             version_stage: DEFAULT_VERSION_STAGE.to_string(),
+            refresh_interval: None,
+            time_source: Arc::new(SystemTimeSource),
         }
     }
 
@@ -57,6 +83,22 @@ impl CacheConfig {
         self.cache_item_ttl = cache_item_ttl;
         self
     }
+
+    /// Enables background (stale-while-revalidate) refresh once cached items are older than
+    /// the given interval, in nanoseconds, while still within their TTL.
+    pub fn refresh_interval(mut self, refresh_interval: u128) -> Self {
+        self.refresh_interval = Some(refresh_interval);
+        self
+    }
+
+    /// Sets the time_source cache configuration option to a different value.
+    ///
+    /// This is primarily useful in tests, where a [`crate::TestTimeSource`] lets TTL/expiry
+    /// logic be exercised deterministically without sleeping.
+    pub fn time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
 }
 
 impl Default for CacheConfig {
@@ -68,6 +110,7 @@ impl Default for CacheConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::time_source::TestTimeSource;
     use std::time;
 
     #[test]
@@ -77,18 +120,26 @@ mod tests {
         assert_eq!(cache_config.cache_item_ttl, DEFAULT_CACHE_ITEM_TTL);
         assert_eq!(cache_config.max_cache_size, DEFAULT_MAX_CACHE_SIZE);
         assert_eq!(cache_config.version_stage, DEFAULT_VERSION_STAGE);
+        assert_eq!(cache_config.refresh_interval, None);
+        assert_eq!(cache_config.time_source.now_nanos() > 0, true);
     }
 
     #[test]
     fn cache_config_custom() {
         let custom_cache_ttl = time::Duration::from_secs(30).as_nanos();
+        let custom_refresh_interval = time::Duration::from_secs(10).as_nanos();
+        let custom_time_source = Arc::new(TestTimeSource::new(123));
         let cache_config = CacheConfig::new()
             .max_cache_size(10)
-            .cache_item_ttl(custom_cache_ttl);
+            .cache_item_ttl(custom_cache_ttl)
+            .refresh_interval(custom_refresh_interval)
+            .time_source(custom_time_source);
 
         assert_eq!(cache_config.cache_item_ttl, custom_cache_ttl);
         assert_eq!(cache_config.max_cache_size, 10);
         assert_eq!(cache_config.version_stage, DEFAULT_VERSION_STAGE);
+        assert_eq!(cache_config.refresh_interval, Some(custom_refresh_interval));
+        assert_eq!(cache_config.time_source.now_nanos(), 123);
     }
 
     #[test]
@@ -98,5 +149,6 @@ mod tests {
         assert_eq!(cache_config.cache_item_ttl, DEFAULT_CACHE_ITEM_TTL);
         assert_eq!(cache_config.max_cache_size, 10);
         assert_eq!(cache_config.version_stage, DEFAULT_VERSION_STAGE);
+        assert_eq!(cache_config.refresh_interval, None);
     }
 }
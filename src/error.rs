@@ -0,0 +1,63 @@
+use std::fmt;
+
+use aws_sdk_config::error::SdkError;
+use aws_sdk_secretsmanager::operation::get_secret_value::GetSecretValueError;
+
+/// Errors that can occur while retrieving a secret from the cache.
+#[derive(Debug)]
+pub enum SecretCacheError {
+    /// The underlying call to AWS Secrets Manager failed.
+    Sdk(SdkError<GetSecretValueError>),
+
+    /// The cached/fetched secret value could not be deserialized into the requested type.
+    Deserialization(serde_json::Error),
+
+    /// The secret was retrieved but has no `secret_string` field set.
+    ///
+    /// This happens when the secret is stored as binary; use `get_secret_binary` instead.
+    MissingSecretString,
+
+    /// The secret was retrieved but has no `secret_binary` field set.
+    ///
+    /// This happens when the secret is stored as a string; use `get_secret_string` instead.
+    MissingSecretBinary,
+}
+
+impl fmt::Display for SecretCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretCacheError::Sdk(e) => write!(f, "{}", e),
+            SecretCacheError::Deserialization(e) => {
+                write!(f, "failed to deserialize secret value: {}", e)
+            }
+            SecretCacheError::MissingSecretString => {
+                write!(f, "secret has no secret_string value; it may be stored as binary")
+            }
+            SecretCacheError::MissingSecretBinary => {
+                write!(f, "secret has no secret_binary value; it may be stored as a string")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SecretCacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SecretCacheError::Sdk(e) => Some(e),
+            SecretCacheError::Deserialization(e) => Some(e),
+            SecretCacheError::MissingSecretString | SecretCacheError::MissingSecretBinary => None,
+        }
+    }
+}
+
+impl From<SdkError<GetSecretValueError>> for SecretCacheError {
+    fn from(e: SdkError<GetSecretValueError>) -> Self {
+        SecretCacheError::Sdk(e)
+    }
+}
+
+impl From<serde_json::Error> for SecretCacheError {
+    fn from(e: serde_json::Error) -> Self {
+        SecretCacheError::Deserialization(e)
+    }
+}
@@ -6,8 +6,10 @@
 //! It is heavily inspired by the [AWS Secrets Manager Go Caching Client](https://github.com/aws/aws-secretsmanager-caching-go)
 //! and the [AWS SDK for Rust](https://github.com/awslabs/aws-sdk-rust).
 //!
-//! The client internally uses an LRU (least-recently used) caching scheme that provides
-//! O(1) insertions and O(1)lookups for cached values.
+//! Secret strings are cached behind a pluggable [`SecretStore`], defaulting to an in-process LRU
+//! (least-recently used) caching scheme that provides O(1) insertions and O(1) lookups for
+//! cached values. Alternate backends can be plugged in via [`SecretCache::new_with_store`].
+//! Binary secrets are cached separately, in an in-process LRU cache.
 
 //! ## Example
 //! ```rust
@@ -34,5 +36,11 @@
 mod cache;
 mod cache_item;
 mod config;
+mod error;
+mod store;
+mod time_source;
 pub use cache::SecretCache;
 pub use config::CacheConfig;
+pub use error::SecretCacheError;
+pub use store::{LruSecretStore, SecretStore};
+pub use time_source::{SystemTimeSource, TestTimeSource, TimeSource};
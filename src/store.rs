@@ -0,0 +1,73 @@
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use lru::LruCache;
+
+use super::cache_item::CacheItem;
+
+/// A TTL-aware storage backend for cached secret string values.
+///
+/// `SecretCache` is generic over this trait, defaulting to [`LruSecretStore`], so alternate
+/// backends (e.g. an unbounded map, a size-and-time-weighted store, or a store shared across a
+/// multi-threaded Lambda runtime) can be plugged in without forking the fetch/caching logic.
+pub trait SecretStore: Send + Sync {
+    /// Returns the cached item for `key`, if present, regardless of whether it has expired.
+    fn get(&self, key: &str) -> Option<CacheItem<String>>;
+
+    /// Inserts or replaces the cached item for `key`.
+    fn put(&self, key: String, item: CacheItem<String>);
+}
+
+/// The default SecretStore, backed by an in-process LRU (least-recently used) cache.
+pub struct LruSecretStore {
+    cache: Mutex<LruCache<String, CacheItem<String>>>,
+}
+
+impl LruSecretStore {
+    /// Returns a new LruSecretStore that evicts least-recently-used entries once
+    /// `max_cache_size` items are stored.
+    pub fn new(max_cache_size: usize) -> Self {
+        let max_cache_size = NonZeroUsize::new(max_cache_size)
+            .unwrap_or(NonZeroUsize::new(1).expect("Default max_cache_size must be non-zero"));
+        LruSecretStore {
+            cache: Mutex::new(LruCache::new(max_cache_size)),
+        }
+    }
+}
+
+impl SecretStore for LruSecretStore {
+    fn get(&self, key: &str) -> Option<CacheItem<String>> {
+        self.cache.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: String, item: CacheItem<String>) {
+        self.cache.lock().unwrap().put(key, item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_secret_store_put_and_get() {
+        let store = LruSecretStore::new(10);
+
+        assert_eq!(store.get("service/secret").is_none(), true);
+
+        store.put("service/secret".to_string(), CacheItem::new("secret_value".to_string(), 0, 0));
+
+        assert_eq!(store.get("service/secret").unwrap().value, "secret_value");
+    }
+
+    #[test]
+    fn lru_secret_store_evicts_least_recently_used() {
+        let store = LruSecretStore::new(1);
+
+        store.put("first".to_string(), CacheItem::new("first_value".to_string(), 0, 0));
+        store.put("second".to_string(), CacheItem::new("second_value".to_string(), 0, 0));
+
+        assert_eq!(store.get("first").is_none(), true);
+        assert_eq!(store.get("second").unwrap().value, "second_value");
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of the current time, used to make TTL/expiry logic in the cache testable without
+/// sleeping.
+pub trait TimeSource: Send + Sync {
+    /// Returns the current time, in nanoseconds elapsed since the unix epoch.
+    fn now_nanos(&self) -> u128;
+}
+
+/// A TimeSource backed by the system clock.
+///
+/// This is the default time source used by [`crate::CacheConfig`].
+pub struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn now_nanos(&self) -> u128 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    }
+}
+
+/// A TimeSource with a manually advanceable clock, for deterministically exercising TTL/expiry
+/// logic in tests without sleeping.
+pub struct TestTimeSource {
+    now_nanos: Mutex<u128>,
+}
+
+impl TestTimeSource {
+    /// Returns a new TestTimeSource starting at the given time, in nanoseconds since the unix
+    /// epoch.
+    pub fn new(start_nanos: u128) -> Self {
+        TestTimeSource {
+            now_nanos: Mutex::new(start_nanos),
+        }
+    }
+
+    /// Advances the clock forward by the given number of nanoseconds.
+    pub fn advance(&self, nanos: u128) {
+        *self.now_nanos.lock().unwrap() += nanos;
+    }
+
+    /// Sets the clock to the given time, in nanoseconds since the unix epoch.
+    pub fn set(&self, now_nanos: u128) {
+        *self.now_nanos.lock().unwrap() = now_nanos;
+    }
+}
+
+impl TimeSource for TestTimeSource {
+    fn now_nanos(&self) -> u128 {
+        *self.now_nanos.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_time_source_advances() {
+        let time_source = SystemTimeSource;
+        let first = time_source.now_nanos();
+        let second = time_source.now_nanos();
+
+        assert!(second >= first);
+    }
+
+    #[test]
+    fn test_time_source_advance() {
+        let time_source = TestTimeSource::new(100);
+
+        assert_eq!(time_source.now_nanos(), 100);
+
+        time_source.advance(50);
+
+        assert_eq!(time_source.now_nanos(), 150);
+    }
+
+    #[test]
+    fn test_time_source_set() {
+        let time_source = TestTimeSource::new(100);
+
+        time_source.set(500);
+
+        assert_eq!(time_source.now_nanos(), 500);
+    }
+}